@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State as AxumState};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json};
+use axum::routing::{get, post};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Mutex};
+
+use crate::dbctx::{Db, Observation};
+use crate::State;
+
+/// What the control API knows about a single task's last run, independent
+/// of the parsed value cached in `State`.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct TaskStatus {
+    pub url: String,
+    pub last_value: Option<String>,
+    pub last_checked_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Everything the control API needs, shared with the task loops via `Arc`.
+#[derive(Clone)]
+pub struct ApiState {
+    pub state: Arc<Mutex<State>>,
+    pub statuses: Arc<Mutex<HashMap<String, TaskStatus>>>,
+    /// One sender per running task id; `POST /tasks/{id}/check` pokes it to
+    /// wake that task's loop immediately instead of waiting for its interval.
+    pub triggers: Arc<Mutex<HashMap<String, mpsc::Sender<()>>>>,
+    /// Append-only observation history backing `/tasks/{id}/history`.
+    pub db: Arc<Db>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    #[serde(default = "default_history_limit")]
+    limit: u32,
+}
+
+fn default_history_limit() -> u32 {
+    20
+}
+
+pub fn router(api_state: ApiState) -> Router {
+    Router::new()
+        .route("/status", get(get_status))
+        .route("/state", get(get_state))
+        .route("/tasks/{id}/check", post(trigger_check))
+        .route("/tasks/{id}/history", get(get_history))
+        .with_state(api_state)
+}
+
+async fn get_status(AxumState(api_state): AxumState<ApiState>) -> Json<HashMap<String, TaskStatus>> {
+    Json(api_state.statuses.lock().await.clone())
+}
+
+async fn get_state(AxumState(api_state): AxumState<ApiState>) -> Json<State> {
+    Json(api_state.state.lock().await.clone())
+}
+
+async fn trigger_check(
+    AxumState(api_state): AxumState<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let sender = api_state.triggers.lock().await.get(&id).cloned();
+    match sender {
+        Some(sender) => match sender.send(()).await {
+            Ok(()) => (StatusCode::ACCEPTED, "check triggered").into_response(),
+            Err(_) => (StatusCode::GONE, "task loop is no longer running").into_response(),
+        },
+        None => (StatusCode::NOT_FOUND, format!("no task with id {:?}", id)).into_response(),
+    }
+}
+
+/// Recent SQLite-backed observation history for a task, most recent first.
+async fn get_history(
+    AxumState(api_state): AxumState<ApiState>,
+    Path(id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    let url = match api_state.statuses.lock().await.get(&id) {
+        Some(status) => status.url.clone(),
+        None => return (StatusCode::NOT_FOUND, format!("no task with id {:?}", id)).into_response(),
+    };
+
+    match api_state.db.recent(&url, query.limit).await {
+        Ok(observations) => Json::<Vec<Observation>>(observations).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One row of `observations`: a single check of a URL at a point in time.
+#[derive(Debug, Clone, Serialize)]
+pub struct Observation {
+    pub parsed_text: String,
+    pub fetched_at: i64,
+    pub changed: bool,
+}
+
+/// SQLite-backed history of every check, kept alongside the in-memory
+/// `State` used for fast comparisons. Unlike `State`, which only remembers
+/// the latest value per URL, this keeps every observation so later features
+/// (e.g. "notify only if changed twice in a row") and historical inspection
+/// have something to query.
+///
+/// The connection sits behind a blocking `std::sync::Mutex` (rather than
+/// `State`'s async one) because every use of it runs inside
+/// `tokio::task::spawn_blocking` -- `rusqlite` calls are blocking disk I/O,
+/// and doing them on a runtime worker thread while holding an async lock
+/// would stall every other task sharing that worker.
+pub struct Db {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Db {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open state database: {:?}", path))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS observations (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                url         TEXT NOT NULL,
+                parsed_text TEXT NOT NULL,
+                fetched_at  INTEGER NOT NULL,
+                changed     INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_observations_url_fetched_at
+                ON observations(url, fetched_at);",
+        )
+        .context("Failed to initialize state database schema")?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// Append a row for this check. Called on every check, regardless of
+    /// whether the parsed text changed.
+    pub async fn record(&self, url: &str, parsed_text: &str, changed: bool) -> Result<()> {
+        let fetched_at = now_unix();
+        let conn = Arc::clone(&self.conn);
+        let url = url.to_string();
+        let parsed_text = parsed_text.to_string();
+
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let conn = conn.lock().map_err(|_| anyhow::anyhow!("observation database mutex poisoned"))?;
+            conn.execute(
+                "INSERT INTO observations (url, parsed_text, fetched_at, changed)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![url, parsed_text, fetched_at, changed],
+            )
+            .context("Failed to record observation")?;
+            Ok(())
+        })
+        .await
+        .context("Observation recording task panicked")?
+    }
+
+    /// Fetch the last `limit` observations for `url`, most recent first.
+    pub async fn recent(&self, url: &str, limit: u32) -> Result<Vec<Observation>> {
+        let conn = Arc::clone(&self.conn);
+        let url = url.to_string();
+
+        tokio::task::spawn_blocking(move || -> Result<Vec<Observation>> {
+            let conn = conn.lock().map_err(|_| anyhow::anyhow!("observation database mutex poisoned"))?;
+            let mut stmt = conn.prepare(
+                "SELECT parsed_text, fetched_at, changed
+                 FROM observations
+                 WHERE url = ?1
+                 ORDER BY id DESC
+                 LIMIT ?2",
+            )?;
+
+            let rows = stmt
+                .query_map(params![url, limit], |row| {
+                    Ok(Observation {
+                        parsed_text: row.get(0)?,
+                        fetched_at: row.get(1)?,
+                        changed: row.get::<_, i64>(2)? != 0,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .context("Failed to read observations")?;
+
+            Ok(rows)
+        })
+        .await
+        .context("Observation history task panicked")?
+    }
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
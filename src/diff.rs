@@ -0,0 +1,191 @@
+/// Number of unchanged lines kept around a change when grouping into hunks.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+struct DiffLine<'a> {
+    op: LineOp,
+    text: &'a str,
+}
+
+/// Compute a unified-diff-style textual delta between `old` and `new`,
+/// line by line. Builds the longest-common-subsequence table over the two
+/// line vectors and backtracks it into ` `/`-`/`+` prefixed lines grouped
+/// into `@@` hunks with a few lines of context either side.
+///
+/// If `old` is empty (first-seen case), the whole of `new` is emitted as
+/// additions.
+pub fn unified_diff(old: &str, new: &str) -> String {
+    if old.is_empty() {
+        return new
+            .lines()
+            .map(|line| format!("+{}", line))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let ops = lcs_ops(&old_lines, &new_lines);
+    render_hunks(&ops)
+}
+
+/// dp[i][j] = length of the LCS of old_lines[i..] and new_lines[j..].
+fn lcs_ops<'a>(old_lines: &[&'a str], new_lines: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let n = old_lines.len();
+    let m = new_lines.len();
+
+    let mut dp = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            dp[i][j] = if old_lines[i] == new_lines[j] {
+                dp[i + 1][j + 1] + 1
+            } else {
+                dp[i + 1][j].max(dp[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_lines[i] == new_lines[j] {
+            ops.push(DiffLine { op: LineOp::Equal, text: old_lines[i] });
+            i += 1;
+            j += 1;
+        } else if dp[i + 1][j] >= dp[i][j + 1] {
+            ops.push(DiffLine { op: LineOp::Delete, text: old_lines[i] });
+            i += 1;
+        } else {
+            ops.push(DiffLine { op: LineOp::Insert, text: new_lines[j] });
+            j += 1;
+        }
+    }
+    while i < n {
+        ops.push(DiffLine { op: LineOp::Delete, text: old_lines[i] });
+        i += 1;
+    }
+    while j < m {
+        ops.push(DiffLine { op: LineOp::Insert, text: new_lines[j] });
+        j += 1;
+    }
+
+    ops
+}
+
+/// Group the backtracked ops into `@@` hunks, keeping `CONTEXT_LINES`
+/// unchanged lines of context around each run of changes and eliding the
+/// rest.
+fn render_hunks(ops: &[DiffLine]) -> String {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < ops.len() {
+        if ops[i].op != LineOp::Equal {
+            let mut end = i;
+            while end < ops.len() && ops[end].op != LineOp::Equal {
+                end += 1;
+            }
+            let start = i.saturating_sub(CONTEXT_LINES);
+            let end = (end + CONTEXT_LINES).min(ops.len());
+            if let Some(last) = hunks.last_mut() {
+                if start <= last.1 {
+                    last.1 = end;
+                    i = end;
+                    continue;
+                }
+            }
+            hunks.push((start, end));
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+
+    // Prefix sums of how many old-side (Equal+Delete) and new-side
+    // (Equal+Insert) lines precede each ops index, so a hunk's `@@` header
+    // can report each side's own start/count instead of the raw ops span.
+    let mut old_pos = Vec::with_capacity(ops.len() + 1);
+    let mut new_pos = Vec::with_capacity(ops.len() + 1);
+    old_pos.push(0usize);
+    new_pos.push(0usize);
+    for line in ops {
+        let last_old = *old_pos.last().unwrap();
+        let last_new = *new_pos.last().unwrap();
+        old_pos.push(last_old + usize::from(line.op != LineOp::Insert));
+        new_pos.push(last_new + usize::from(line.op != LineOp::Delete));
+    }
+
+    let mut out = Vec::new();
+    for (start, end) in hunks {
+        let old_start = old_pos[start];
+        let old_count = old_pos[end] - old_start;
+        let new_start = new_pos[start];
+        let new_count = new_pos[end] - new_start;
+        out.push(format!(
+            "@@ -{},{} +{},{} @@",
+            old_start + 1,
+            old_count,
+            new_start + 1,
+            new_count
+        ));
+        for line in &ops[start..end] {
+            let prefix = match line.op {
+                LineOp::Equal => ' ',
+                LineOp::Delete => '-',
+                LineOp::Insert => '+',
+            };
+            out.push(format!("{}{}", prefix, line.text));
+        }
+    }
+
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_seen_emits_all_additions() {
+        let diff = unified_diff("", "a\nb");
+        assert_eq!(diff, "+a\n+b");
+    }
+
+    #[test]
+    fn single_line_substitution_reports_matching_old_new_counts() {
+        // A 1-line substitution in a 5-line file is still 5 lines on each
+        // side -- the header must not just echo the raw ops-array span.
+        let diff = unified_diff("a\nb\nc\nd\ne", "a\nX\nc\nd\ne");
+        let header = diff.lines().next().unwrap();
+        assert_eq!(header, "@@ -1,5 +1,5 @@");
+        assert!(diff.contains("-b"));
+        assert!(diff.contains("+X"));
+    }
+
+    #[test]
+    fn deletion_only_diff_has_smaller_new_count() {
+        let diff = unified_diff("a\nb\nc", "a\nc");
+        let header = diff.lines().next().unwrap();
+        assert_eq!(header, "@@ -1,3 +1,2 @@");
+        assert!(diff.contains("-b"));
+    }
+
+    #[test]
+    fn far_apart_changes_produce_separate_hunks() {
+        let old = (0..20).map(|n| n.to_string()).collect::<Vec<_>>().join("\n");
+        let new_lines: Vec<String> = (0..20)
+            .map(|n| if n == 1 || n == 18 { format!("{}-changed", n) } else { n.to_string() })
+            .collect();
+        let new = new_lines.join("\n");
+
+        let diff = unified_diff(&old, &new);
+        let hunk_count = diff.lines().filter(|l| l.starts_with("@@")).count();
+        assert_eq!(hunk_count, 2, "expected two separate hunks, got:\n{diff}");
+    }
+}
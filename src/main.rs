@@ -10,6 +10,15 @@ use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 use tokio::time::{self, Duration};
 
+mod api;
+mod dbctx;
+mod diff;
+mod notifier;
+mod script;
+mod template;
+use notifier::NotifierConfig;
+use tokio::sync::{mpsc, Mutex};
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
@@ -24,38 +33,156 @@ struct Cli {
     /// State file path
     #[arg(short, long, default_value = "upi-state.json")]
     state_file: PathBuf,
+
+    /// SQLite database recording every observation (timestamp, parsed text,
+    /// whether it changed), for history beyond the latest value in `State`.
+    #[arg(long, rename_all = "kebab-case", default_value = "upi-history.db")]
+    history_db: PathBuf,
+
+    /// Address to bind the control/status HTTP API to (e.g. 127.0.0.1:9090).
+    /// Left unset, no API is started.
+    #[arg(long)]
+    listen: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 struct Task {
+    /// Stable identifier used by the control API and config reloads; falls
+    /// back to `url` when not set.
+    #[serde(default)]
+    id: Option<String>,
     url: String,
-    parse: String,
+    #[serde(default)]
+    parse: Option<String>,
+    /// In-process Rhai script evaluated with `body` bound to the response
+    /// text; mutually exclusive with `parse`. Avoids spawning a shell where
+    /// none exists (e.g. Windows) and the per-check process spawn cost.
+    #[serde(default, rename = "parse-script")]
+    parse_script: Option<String>,
     command: String,
     #[serde(rename = "check-every")]
     check_every: u64,
+    /// Additional sinks to fan a detected change out to, beyond `command`.
+    #[serde(default)]
+    notifiers: Vec<NotifierConfig>,
+    /// Per-request timeout in seconds. Defaults to 30s so a slow endpoint
+    /// can't hang the whole task loop.
+    #[serde(default = "default_timeout_secs")]
+    timeout: u64,
+    /// Number of attempts before giving up on a fetch.
+    #[serde(default = "default_retries")]
+    retries: u32,
+    /// Base delay in seconds for exponential backoff between retries
+    /// (attempt N waits `retry-backoff * 2^(N-1)` seconds).
+    #[serde(default = "default_retry_backoff_secs", rename = "retry-backoff")]
+    retry_backoff: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff_secs() -> u64 {
+    1
+}
+
+impl Task {
+    /// Stable key used to track this task's running loop and status across
+    /// config reloads; falls back to the URL when `id` isn't set.
+    fn task_id(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.url)
+    }
+
+    fn validate(&self) -> Result<()> {
+        match (&self.parse, &self.parse_script) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!(
+                    "Task for {} specifies both `parse` and `parse-script`; only one is allowed",
+                    self.url
+                )
+            }
+            (None, None) => {
+                anyhow::bail!(
+                    "Task for {} must specify one of `parse` or `parse-script`",
+                    self.url
+                )
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct AppConfig {
     #[serde(default, rename = "global-check-every")]
     global_check_every: Option<u64>,
+    /// Default notifiers applied to every task, in addition to any the task
+    /// lists itself; merged into each `Task::notifiers` by `load_config`.
+    #[serde(default)]
+    notifiers: Vec<NotifierConfig>,
     #[serde(default)]
     tasks: Vec<Task>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+/// Cached per-URL state: the last parsed output plus the response
+/// validators needed to make conditional requests on the next check.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct UrlState {
+    parsed_text: String,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 struct State {
-    // Map URL to the last parsed output
+    // Map URL to its cached parsed output and conditional-request validators
+    results: HashMap<String, UrlState>,
+}
+
+/// Pre-ETag on-disk shape of `State`, back when `results` mapped a URL
+/// straight to its parsed text instead of to a `UrlState`.
+#[derive(Deserialize)]
+struct LegacyState {
     results: HashMap<String, String>,
 }
 
 impl State {
+    /// Load the state file, migrating it if it's still in the pre-ETag
+    /// shape and logging (rather than silently discarding it) if it's in
+    /// neither shape, so an upgrade doesn't quietly treat every URL as
+    /// first-seen and re-fire every task's command/notifiers.
     fn load(path: &Path) -> Self {
-        if let Ok(content) = std::fs::read_to_string(path) {
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            Self::default()
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return Self::default(),
+        };
+
+        if let Ok(state) = serde_json::from_str::<Self>(&content) {
+            return state;
+        }
+
+        if let Ok(legacy) = serde_json::from_str::<LegacyState>(&content) {
+            println!("Migrating state file {:?} from the pre-ETag format", path);
+            return Self {
+                results: legacy
+                    .results
+                    .into_iter()
+                    .map(|(url, parsed_text)| (url, UrlState { parsed_text, ..Default::default() }))
+                    .collect(),
+            };
         }
+
+        println!(
+            "Warning: Failed to parse state file {:?}; starting with empty state (every URL will look first-seen)",
+            path
+        );
+        Self::default()
     }
 
     fn save(&self, path: &Path) -> Result<()> {
@@ -65,61 +192,211 @@ impl State {
     }
 }
 
-async fn run_task(task: &Task, state: &mut State, client: &reqwest::Client) -> Result<bool> {
+/// Outcome of fetching a task's URL: either the server told us nothing
+/// changed (`304 Not Modified`), or we got a fresh body plus whatever
+/// validators it came with.
+enum FetchOutcome {
+    NotModified,
+    Body {
+        text: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+/// Fetch `task.url`, honoring `task.timeout` and sending conditional
+/// request headers from `cached` so an unchanged resource can short-circuit
+/// as a `304` without re-downloading or re-parsing. A failed attempt (a
+/// request error, a non-success status or a failed body read) is retried,
+/// for `task.retries` attempts in all (at least one), with exponential
+/// backoff: after failed attempt N it waits `task.retry_backoff * 2^(N-1)`
+/// seconds.
+async fn fetch_with_retries(
+    client: &reqwest::Client,
+    task: &Task,
+    cached: Option<&UrlState>,
+) -> Result<FetchOutcome> {
+    let mut attempt = 0u32;
+    loop {
+        attempt += 1;
+
+        let mut req = client
+            .get(&task.url)
+            .timeout(Duration::from_secs(task.timeout));
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                req = req.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let outcome = match req.send().await {
+            Ok(resp) if resp.status() == reqwest::StatusCode::NOT_MODIFIED => {
+                Ok(FetchOutcome::NotModified)
+            }
+            Ok(resp) if resp.status().is_success() => {
+                let etag = header_str(&resp, reqwest::header::ETAG);
+                let last_modified = header_str(&resp, reqwest::header::LAST_MODIFIED);
+                match resp.text().await {
+                    Ok(text) => Ok(FetchOutcome::Body { text, etag, last_modified }),
+                    Err(e) => Err(anyhow::Error::new(e)
+                        .context(format!("Failed to read body of URL {}", task.url))),
+                }
+            }
+            Ok(resp) => Err(anyhow::anyhow!(
+                "Failed to fetch URL {}: {}",
+                task.url,
+                resp.status()
+            )),
+            Err(e) => Err(anyhow::Error::new(e).context(format!("Failed to fetch URL {}", task.url))),
+        };
+
+        match outcome {
+            Ok(outcome) => return Ok(outcome),
+            Err(e) if attempt >= task.retries => return Err(e),
+            Err(e) => {
+                let backoff = task.retry_backoff.saturating_mul(1 << (attempt - 1));
+                println!(
+                    "Fetch of {} failed ({}), retrying in {}s (attempt {}/{})",
+                    task.url, e, backoff, attempt, task.retries
+                );
+                time::sleep(Duration::from_secs(backoff)).await;
+            }
+        }
+    }
+}
+
+fn header_str(resp: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+    resp.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+}
+
+/// Run a single check for `task`: fetch, parse, compare, and (on a change)
+/// render and run the command plus any notifiers.
+///
+/// `state`'s lock is only taken briefly, to copy the cached entry before the
+/// fetch and to insert the new value after parsing; fetching (with its
+/// retry backoff), parsing, and running the command/notifiers can all take
+/// arbitrarily long and must not stall every other task's loop or the
+/// control API's `GET /state` for that long.
+async fn run_task(
+    task: &Task,
+    state: &Mutex<State>,
+    client: &reqwest::Client,
+    engine: &rhai::Engine,
+    db: &dbctx::Db,
+) -> Result<bool> {
     println!("Checking URL: {}", task.url);
-    
-    // 1. Download
-    let resp = client.get(&task.url).send().await?;
-    if !resp.status().is_success() {
-        anyhow::bail!("Failed to fetch URL {}: {}", task.url, resp.status());
-    }
-    let response = resp.text().await?;
-    
-    // 2. Parse (using the provided command via shell)
-    let mut child = Command::new("sh")
-        .arg("-c")
-        .arg(&task.parse)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .context("Failed to spawn parse command")?;
-
-    let mut stdin = child.stdin.take().expect("Failed to open stdin");
-    stdin.write_all(response.as_bytes()).await?;
-    drop(stdin);
-
-    let output = child.wait_with_output().await?;
-    if !output.status.success() {
-        let err = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Parse command failed: {}", err);
-    }
-    
-    let parsed_text = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    // println!("Parsed text: '{}'", parsed_text);
-    
-    // 3. Compare with state
-    let last_result = state.results.get(&task.url);
-    let changed = match last_result {
-        Some(last) => last != &parsed_text,
+
+    // 1. Download, reusing cached ETag/Last-Modified for a conditional request.
+    let cached = state.lock().await.results.get(&task.url).cloned();
+    let (response, etag, last_modified) =
+        match fetch_with_retries(client, task, cached.as_ref()).await? {
+            FetchOutcome::NotModified => {
+                println!("Not modified: {}", task.url);
+                let parsed_text = cached.map(|c| c.parsed_text).unwrap_or_default();
+                if let Err(e) = db.record(&task.url, &parsed_text, false).await {
+                    println!("Warning: Failed to record observation for {}: {}", task.url, e);
+                }
+                return Ok(false);
+            }
+            FetchOutcome::Body { text, etag, last_modified } => (text, etag, last_modified),
+        };
+
+    // 2. Parse, either via an in-process Rhai script or by shelling out.
+    // A parse-script can also expose named capture vars (see
+    // `script::eval_parse_script`) for the command/notifier templates below.
+    let (parsed_text, extra_vars) = if let Some(parse_script) = &task.parse_script {
+        script::eval_parse_script(engine, parse_script, &response)
+            .with_context(|| format!("parse-script failed for {}", task.url))?
+    } else {
+        let parse = task
+            .parse
+            .as_ref()
+            .expect("Task::validate guarantees parse or parse-script is set");
+
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(parse)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to spawn parse command")?;
+
+        let mut stdin = child.stdin.take().expect("Failed to open stdin");
+        stdin.write_all(response.as_bytes()).await?;
+        drop(stdin);
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Parse command failed: {}", err);
+        }
+
+        (String::from_utf8_lossy(&output.stdout).trim().to_string(), HashMap::new())
+    };
+
+    // 3. Compare with the cached entry copied in step 1, then persist the
+    // new value; only the insert takes the lock.
+    let changed = match &cached {
+        Some(last) => last.parsed_text != parsed_text,
         None => true,
     };
+    let old_text = cached.map(|c| c.parsed_text).unwrap_or_default();
+
+    state.lock().await.results.insert(
+        task.url.clone(),
+        UrlState {
+            parsed_text: parsed_text.clone(),
+            etag,
+            last_modified,
+        },
+    );
+
+    if let Err(e) = db.record(&task.url, &parsed_text, changed).await {
+        println!("Warning: Failed to record observation for {}: {}", task.url, e);
+    }
 
     if changed {
-        println!("Change detected for {}. Running command: {}", task.url, task.command);
-        state.results.insert(task.url.clone(), parsed_text.clone());
-        
+        let unified_diff = diff::unified_diff(&old_text, &parsed_text);
+
+        // Named captures the parse step exposed go in first so the
+        // reserved names below always win on a collision.
+        let mut vars = extra_vars;
+        vars.insert("url".to_string(), task.url.clone());
+        vars.insert("old".to_string(), old_text.clone());
+        vars.insert("new".to_string(), parsed_text.clone());
+        vars.insert("ts".to_string(), chrono::Utc::now().to_rfc3339());
+        vars.insert("diff".to_string(), unified_diff.clone());
+
+        let rendered_command = template::render(&task.command, &vars)
+            .with_context(|| format!("Failed to render command template for {}", task.url))?;
+        println!("Change detected for {}. Running command: {}", task.url, rendered_command);
+
         // 4. Run command
         let cmd_status = Command::new("sh")
             .arg("-c")
-            .arg(&task.command)
+            .arg(&rendered_command)
             .env("UPI_PARSED", &parsed_text)
+            .env("UPI_DIFF", &unified_diff)
             .status()
             .await?;
-            
+
         if !cmd_status.success() {
             println!("Warning: Command for {} exited with error", task.url);
         }
+
+        // 5. Fan out to any configured notifiers, independently of the
+        // command above (a failure in one must not block the others).
+        if !task.notifiers.is_empty() {
+            notifier::notify_all(&task.notifiers, client, &vars).await;
+        }
+
         return Ok(true);
     } else {
         println!("No change for {}", task.url);
@@ -128,33 +405,292 @@ async fn run_task(task: &Task, state: &mut State, client: &reqwest::Client) -> R
     Ok(false)
 }
 
+/// Everything a task loop (or the control API) needs a handle to; cloning
+/// just bumps `Arc` refcounts, so it's threaded through by value.
+#[derive(Clone)]
+struct Shared {
+    client: reqwest::Client,
+    engine: Arc<rhai::Engine>,
+    db: Arc<dbctx::Db>,
+    state: Arc<Mutex<State>>,
+    state_file: PathBuf,
+    statuses: Arc<Mutex<HashMap<String, api::TaskStatus>>>,
+    triggers: Arc<Mutex<HashMap<String, mpsc::Sender<()>>>>,
+    url_locks: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+}
+
+/// Serialize `run_task` calls for the same URL. The per-task loop and the
+/// global loop (or two `Task` entries that happen to share a URL) can
+/// otherwise both read the same cached value, both decide `changed`, and
+/// both fire the command/notifiers -- the per-URL lock makes a check cycle
+/// atomic with respect to every other loop checking that URL, while leaving
+/// different URLs free to run concurrently.
+async fn lock_url(locks: &Mutex<HashMap<String, Arc<Mutex<()>>>>, url: &str) -> tokio::sync::OwnedMutexGuard<()> {
+    let lock = Arc::clone(locks.lock().await.entry(url.to_string()).or_insert_with(|| Arc::new(Mutex::new(()))));
+    lock.lock_owned().await
+}
+
+/// A task's running loop, tracked so SIGHUP can diff it against a reloaded
+/// config and decide whether to leave it alone, restart it, or stop it.
+struct RunningTask {
+    task: Task,
+    abort: tokio::task::AbortHandle,
+}
+
+struct RunningGlobal {
+    interval_secs: u64,
+    tasks: Vec<Task>,
+    abort: tokio::task::AbortHandle,
+}
+
+/// Read and validate the YAML config at `path`, applying the CLI's
+/// `--global-check-every` override. Shared by the initial load and every
+/// SIGHUP reload so they treat a bad config file the same way.
+fn load_config(path: &Path, global_check_every_override: Option<u64>) -> Result<AppConfig> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {:?}", path))?;
+    let mut config = serde_yaml::from_str::<AppConfig>(&content)
+        .with_context(|| "Failed to parse YAML config")?;
+
+    if global_check_every_override.is_some() {
+        config.global_check_every = global_check_every_override;
+    }
+    for task in &mut config.tasks {
+        task.validate()?;
+        task.notifiers = config.notifiers.iter().cloned().chain(task.notifiers.drain(..)).collect();
+    }
+
+    Ok(config)
+}
+
+/// Record the outcome of a check so `GET /status` has something to report.
+async fn update_status(
+    statuses: &Mutex<HashMap<String, api::TaskStatus>>,
+    id: &str,
+    url: &str,
+    value: Option<String>,
+    result: &Result<bool>,
+) {
+    let mut statuses = statuses.lock().await;
+    let entry = statuses
+        .entry(id.to_string())
+        .or_insert_with(|| api::TaskStatus { url: url.to_string(), ..Default::default() });
+    entry.last_checked_at = Some(chrono::Utc::now().to_rfc3339());
+    match result {
+        Ok(_) => {
+            entry.last_value = value;
+            entry.last_error = None;
+        }
+        Err(e) => entry.last_error = Some(e.to_string()),
+    }
+}
+
+/// Run a single task on its own interval, forever, waking early whenever
+/// `POST /tasks/{id}/check` sends a trigger.
+async fn run_task_loop(task: Task, shared: Shared, mut trigger_rx: mpsc::Receiver<()>) {
+    let id = task.task_id().to_string();
+    let mut interval = time::interval(Duration::from_secs(task.check_every));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            Some(()) = trigger_rx.recv() => {
+                println!("Immediate check requested for {}", id);
+            }
+        }
+
+        let result = {
+            let _url_guard = lock_url(&shared.url_locks, &task.url).await;
+            run_task(&task, &shared.state, &shared.client, &shared.engine, &shared.db).await
+        };
+        let value = shared.state.lock().await.results.get(&task.url).map(|u| u.parsed_text.clone());
+        let changed = *result.as_ref().unwrap_or(&false);
+
+        update_status(&shared.statuses, &id, &task.url, value, &result).await;
+
+        match result {
+            Ok(_) if changed => {
+                let s = shared.state.lock().await;
+                if let Err(e) = s.save(&shared.state_file) {
+                    println!("Error saving state: {}", e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => println!("Error running task {}: {}", task.url, e),
+        }
+    }
+}
+
+/// Spawn `task`'s loop into `set`, registering its trigger channel and an
+/// initial status entry, and return the handle needed to stop it later.
+async fn spawn_task_loop(set: &mut tokio::task::JoinSet<()>, task: Task, shared: Shared) -> tokio::task::AbortHandle {
+    let id = task.task_id().to_string();
+    let (trigger_tx, trigger_rx) = mpsc::channel(4);
+    shared.triggers.lock().await.insert(id.clone(), trigger_tx);
+    shared
+        .statuses
+        .lock()
+        .await
+        .entry(id)
+        .or_insert_with(|| api::TaskStatus { url: task.url.clone(), ..Default::default() });
+
+    set.spawn(async move { run_task_loop(task, shared, trigger_rx).await })
+}
+
+/// Run every task once per `interval_secs`, matching the old "global check"
+/// behavior of checking everything on a single shared clock.
+async fn run_global_loop(tasks: Vec<Task>, interval_secs: u64, shared: Shared) {
+    let mut interval = time::interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        println!("Global check triggered...");
+        let mut any_changed = false;
+        for task in &tasks {
+            let result = {
+                let _url_guard = lock_url(&shared.url_locks, &task.url).await;
+                run_task(task, &shared.state, &shared.client, &shared.engine, &shared.db).await
+            };
+            let value = shared.state.lock().await.results.get(&task.url).map(|u| u.parsed_text.clone());
+
+            update_status(&shared.statuses, task.task_id(), &task.url, value, &result).await;
+            match result {
+                Ok(changed) => {
+                    if changed {
+                        any_changed = true;
+                    }
+                }
+                Err(e) => println!("Error running task {} (global): {}", task.url, e),
+            }
+        }
+        if any_changed {
+            let s = shared.state.lock().await;
+            if let Err(e) = s.save(&shared.state_file) {
+                println!("Error saving state: {}", e);
+            }
+        }
+    }
+}
+
+/// Reconcile the running task set with a freshly reloaded config: stop
+/// loops for tasks that were removed or whose definition changed, start
+/// loops for ones that are new, and respawn the global loop if its
+/// interval or the task set it iterates changed. Existing `State`/status
+/// entries are left untouched, even for tasks that get stopped, so history
+/// isn't lost on a reload.
+async fn reconcile(
+    set: &mut tokio::task::JoinSet<()>,
+    running: &mut HashMap<String, RunningTask>,
+    global: &mut Option<RunningGlobal>,
+    new_config: &AppConfig,
+    shared: &Shared,
+) {
+    let new_by_id: HashMap<&str, &Task> =
+        new_config.tasks.iter().map(|t| (t.task_id(), t)).collect();
+
+    let stale: Vec<String> = running
+        .iter()
+        .filter(|(id, running)| match new_by_id.get(id.as_str()) {
+            None => true,
+            Some(new_task) => *new_task != &running.task,
+        })
+        .map(|(id, _)| id.clone())
+        .collect();
+
+    for id in stale {
+        if let Some(running_task) = running.remove(&id) {
+            running_task.abort.abort();
+        }
+        shared.triggers.lock().await.remove(&id);
+        println!("Stopped task {} (removed or changed)", id);
+    }
+
+    for task in &new_config.tasks {
+        let id = task.task_id().to_string();
+        if running.contains_key(&id) {
+            continue;
+        }
+        println!("Starting task {}", id);
+        let abort = spawn_task_loop(set, task.clone(), shared.clone()).await;
+        running.insert(id, RunningTask { task: task.clone(), abort });
+    }
+
+    let wanted_global = new_config.global_check_every.filter(|secs| *secs > 0);
+    let global_stale = match global.as_ref() {
+        None => wanted_global.is_some(),
+        Some(g) => g.interval_secs != wanted_global.unwrap_or(0) || g.tasks != new_config.tasks,
+    };
+    if global_stale {
+        if let Some(g) = global.take() {
+            g.abort.abort();
+        }
+        if let Some(secs) = wanted_global {
+            println!("Restarting global check loop every {}s", secs);
+            let tasks = new_config.tasks.clone();
+            let shared = shared.clone();
+            let abort = {
+                let tasks = tasks.clone();
+                set.spawn(async move { run_global_loop(tasks, secs, shared).await })
+            };
+            *global = Some(RunningGlobal { interval_secs: secs, tasks, abort });
+        }
+    }
+}
+
+enum ControlEvent {
+    Shutdown,
+    Reload,
+}
+
+/// Listen for SIGTERM/SIGINT (shutdown) and SIGHUP (reload), forwarding
+/// them to `main`'s select loop. On non-Unix platforms only Ctrl-C
+/// (shutdown) is available.
+#[cfg(unix)]
+fn spawn_signal_listener() -> mpsc::Receiver<ControlEvent> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (tx, rx) = mpsc::channel(8);
+    tokio::spawn(async move {
+        let mut sigterm = signal(SignalKind::terminate()).expect("failed to register SIGTERM handler");
+        let mut sigint = signal(SignalKind::interrupt()).expect("failed to register SIGINT handler");
+        let mut sighup = signal(SignalKind::hangup()).expect("failed to register SIGHUP handler");
+        loop {
+            tokio::select! {
+                _ = sigterm.recv() => { let _ = tx.send(ControlEvent::Shutdown).await; }
+                _ = sigint.recv() => { let _ = tx.send(ControlEvent::Shutdown).await; }
+                _ = sighup.recv() => { let _ = tx.send(ControlEvent::Reload).await; }
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(not(unix))]
+fn spawn_signal_listener() -> mpsc::Receiver<ControlEvent> {
+    let (tx, rx) = mpsc::channel(8);
+    tokio::spawn(async move {
+        let _ = tokio::signal::ctrl_c().await;
+        let _ = tx.send(ControlEvent::Shutdown).await;
+    });
+    rx
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::init();
     let cli = Cli::parse();
-    
-    let mut config = if let Some(config_path) = cli.config {
-        let content = std::fs::read_to_string(&config_path)
-            .with_context(|| format!("Failed to read config file: {:?}", config_path))?;
-        serde_yaml::from_str::<AppConfig>(&content)
-            .with_context(|| "Failed to parse YAML config")?
-    } else {
-        AppConfig {
+
+    let config_path = cli.config.clone();
+    let config = match &config_path {
+        Some(path) => load_config(path, cli.global_check_every)?,
+        None => AppConfig {
             global_check_every: cli.global_check_every,
+            notifiers: vec![],
             tasks: vec![],
-        }
+        },
     };
 
-    // If CLI provided a global check interval, it overrides config
-    if cli.global_check_every.is_some() {
-        config.global_check_every = cli.global_check_every;
-    }
-
     let state_file = cli.state_file.clone();
-    
-    let client = reqwest::Client::builder()
-        .user_agent("upi/0.1.0")
-        .build()?;
+
+    let client = reqwest::Client::builder().user_agent("upi/0.1.0").build()?;
 
     if config.tasks.is_empty() {
         println!("No tasks defined in config. Exiting.");
@@ -163,73 +699,101 @@ async fn main() -> Result<()> {
 
     println!("Starting upi with {} tasks", config.tasks.len());
 
-    // We'll spawn a task for each task interval, and optionally a global one.
-    // However, to keep it simple and avoid concurrent state writes, we can use a single loop 
-    // or a shared state with a mutex.
-    
-    use tokio::sync::Mutex;
-    let state = Arc::new(Mutex::new(State::load(&state_file)));
-    
+    let shared = Shared {
+        client: client.clone(),
+        engine: Arc::new(script::build_engine()),
+        db: Arc::new(dbctx::Db::open(&cli.history_db)?),
+        state: Arc::new(Mutex::new(State::load(&state_file))),
+        state_file: state_file.clone(),
+        statuses: Arc::new(Mutex::new(HashMap::new())),
+        triggers: Arc::new(Mutex::new(HashMap::new())),
+        url_locks: Arc::new(Mutex::new(HashMap::new())),
+    };
+
     let mut set = tokio::task::JoinSet::new();
+    let mut running: HashMap<String, RunningTask> = HashMap::new();
 
-    // Spawn individual tasks
     for task in config.tasks.clone() {
-        let state = Arc::clone(&state);
-        let state_file = state_file.clone();
-        let client = client.clone();
+        let id = task.task_id().to_string();
+        let abort = spawn_task_loop(&mut set, task.clone(), shared.clone()).await;
+        running.insert(id, RunningTask { task, abort });
+    }
+
+    let mut global: Option<RunningGlobal> = None;
+    if let Some(secs) = config.global_check_every {
+        if secs > 0 {
+            let tasks = config.tasks.clone();
+            let shared = shared.clone();
+            let abort = {
+                let tasks = tasks.clone();
+                set.spawn(async move { run_global_loop(tasks, secs, shared).await })
+            };
+            global = Some(RunningGlobal { interval_secs: secs, tasks, abort });
+        }
+    }
+
+    if let Some(addr) = &cli.listen {
+        let api_state = api::ApiState {
+            state: Arc::clone(&shared.state),
+            statuses: Arc::clone(&shared.statuses),
+            triggers: Arc::clone(&shared.triggers),
+            db: Arc::clone(&shared.db),
+        };
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind control API to {}", addr))?;
+        println!("Control API listening on {}", addr);
+        let router = api::router(api_state);
         set.spawn(async move {
-            let mut interval = time::interval(Duration::from_secs(task.check_every));
-            loop {
-                interval.tick().await;
-                let mut s = state.lock().await;
-                match run_task(&task, &mut s, &client).await {
-                    Ok(changed) => {
-                        if changed {
-                            if let Err(e) = s.save(&state_file) {
-                                println!("Error saving state: {}", e);
-                            }
-                        }
-                    }
-                    Err(e) => println!("Error running task {}: {}", task.url, e),
-                }
+            if let Err(e) = axum::serve(listener, router).await {
+                println!("Control API server error: {}", e);
             }
         });
     }
 
-    // Spawn global task if enabled
-    if let Some(global_secs) = config.global_check_every {
-        if global_secs > 0 {
-            let state = Arc::clone(&state);
-            let state_file = state_file.clone();
-            let tasks = config.tasks.clone();
-            let client = client.clone();
-            set.spawn(async move {
-                let mut interval = time::interval(Duration::from_secs(global_secs));
-                loop {
-                    interval.tick().await;
-                    println!("Global check triggered...");
-                    let mut s = state.lock().await;
-                    let mut any_changed = false;
-                    for task in &tasks {
-                        match run_task(task, &mut s, &client).await {
-                            Ok(changed) => if changed { any_changed = true; },
-                            Err(e) => println!("Error running task {} (global): {}", task.url, e),
-                        }
+    let mut ctrl_rx = spawn_signal_listener();
+
+    loop {
+        tokio::select! {
+            res = set.join_next(), if !set.is_empty() => {
+                // A cancelled join is just reconcile() aborting a removed/changed
+                // task's loop (or shutdown's abort_all below) -- not a crash.
+                if let Some(Err(e)) = res {
+                    if !e.is_cancelled() {
+                        println!("A task loop ended unexpectedly: {}", e);
                     }
-                    if any_changed {
+                }
+            }
+            Some(event) = ctrl_rx.recv() => {
+                match event {
+                    ControlEvent::Shutdown => {
+                        println!("Shutdown requested; persisting state and exiting...");
+                        set.abort_all();
+                        while set.join_next().await.is_some() {}
+                        let s = shared.state.lock().await;
                         if let Err(e) = s.save(&state_file) {
-                            println!("Error saving state: {}", e);
+                            println!("Error saving state on shutdown: {}", e);
+                        }
+                        break;
+                    }
+                    ControlEvent::Reload => {
+                        let Some(path) = &config_path else {
+                            println!("SIGHUP received but no --config file was given; nothing to reload");
+                            continue;
+                        };
+                        println!("Reloading config from {:?}", path);
+                        match load_config(path, cli.global_check_every) {
+                            Ok(new_config) => {
+                                reconcile(&mut set, &mut running, &mut global, &new_config, &shared).await;
+                            }
+                            Err(e) => println!("Failed to reload config: {}", e),
                         }
                     }
                 }
-            });
+            }
+            else => break,
         }
     }
 
-    // Wait for all tasks (they run forever)
-    while let Some(res) = set.join_next().await {
-        res?;
-    }
-
     Ok(())
 }
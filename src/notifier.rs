@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::template;
+
+/// How a detected change should be announced.
+///
+/// A task (or the config root, for a default) can list several of these so a
+/// single change fans out to more than one sink.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum NotifierConfig {
+    /// Run a shell command, mirroring `Task::command`.
+    Command { command: String },
+    /// POST a JSON payload describing the change.
+    Webhook { url: String },
+    /// Send the change over SMTP.
+    Email {
+        smtp_host: String,
+        #[serde(default = "default_smtp_port")]
+        smtp_port: u16,
+        from: String,
+        to: String,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    url: &'a str,
+    old: &'a str,
+    new: &'a str,
+    timestamp: String,
+}
+
+/// Invoke every configured notifier for a change, logging failures per
+/// notifier instead of letting one bad sink abort the rest.
+///
+/// `vars` carries the same named placeholders (`url`, `old`, `new`, `ts`,
+/// ...) used to render `Task::command`, so notifier commands and webhook
+/// URLs can reference them too.
+pub async fn notify_all(notifiers: &[NotifierConfig], client: &reqwest::Client, vars: &HashMap<String, String>) {
+    let url = vars.get("url").map(String::as_str).unwrap_or_default();
+    let old = vars.get("old").map(String::as_str).unwrap_or_default();
+    let new = vars.get("new").map(String::as_str).unwrap_or_default();
+
+    for notifier in notifiers {
+        let result = match notifier {
+            NotifierConfig::Command { command } => run_command(command, vars).await,
+            NotifierConfig::Webhook { url: hook_url } => {
+                send_webhook(client, hook_url, vars, url, old, new).await
+            }
+            NotifierConfig::Email { .. } => send_email(notifier, url, old, new).await,
+        };
+
+        if let Err(e) = result {
+            println!("Notifier failed for {}: {}", url, e);
+        }
+    }
+}
+
+async fn run_command(command: &str, vars: &HashMap<String, String>) -> Result<()> {
+    let rendered = template::render(command, vars).context("Failed to render notifier command template")?;
+
+    let status = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(&rendered)
+        .env("UPI_PARSED", vars.get("new").map(String::as_str).unwrap_or_default())
+        .env("UPI_OLD", vars.get("old").map(String::as_str).unwrap_or_default())
+        .env("UPI_URL", vars.get("url").map(String::as_str).unwrap_or_default())
+        .env("UPI_DIFF", vars.get("diff").map(String::as_str).unwrap_or_default())
+        .status()
+        .await
+        .context("Failed to spawn notifier command")?;
+
+    if !status.success() {
+        anyhow::bail!("Notifier command exited with {}", status);
+    }
+    Ok(())
+}
+
+async fn send_webhook(
+    client: &reqwest::Client,
+    hook_url: &str,
+    vars: &HashMap<String, String>,
+    url: &str,
+    old: &str,
+    new: &str,
+) -> Result<()> {
+    let hook_url = template::render(hook_url, vars).context("Failed to render webhook URL template")?;
+    let payload = WebhookPayload {
+        url,
+        old,
+        new,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let resp = client
+        .post(hook_url)
+        .json(&payload)
+        .send()
+        .await
+        .context("Failed to send webhook")?;
+
+    if !resp.status().is_success() {
+        anyhow::bail!("Webhook returned {}", resp.status());
+    }
+    Ok(())
+}
+
+async fn send_email(notifier: &NotifierConfig, url: &str, old: &str, new: &str) -> Result<()> {
+    use lettre::message::Message;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+
+    let NotifierConfig::Email { smtp_host, smtp_port, from, to, username, password } = notifier else {
+        unreachable!("send_email called with a non-Email notifier");
+    };
+
+    let body = format!("{} changed.\n\nOld:\n{}\n\nNew:\n{}", url, old, new);
+    let email = Message::builder()
+        .from(from.parse().context("Invalid From address")?)
+        .to(to.parse().context("Invalid To address")?)
+        .subject(format!("upi: change detected for {}", url))
+        .body(body)
+        .context("Failed to build email")?;
+
+    let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(smtp_host)
+        .context("Failed to configure SMTP relay")?
+        .port(*smtp_port);
+
+    if let (Some(user), Some(pass)) = (username.as_deref(), password.as_deref()) {
+        builder = builder.credentials(Credentials::new(user.to_string(), pass.to_string()));
+    }
+
+    let mailer = builder.build();
+    mailer
+        .send(email)
+        .await
+        .context("Failed to send notification email")?;
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use rhai::{Dynamic, Engine};
+use std::collections::HashMap;
+
+/// Build the Rhai engine used to evaluate `Task::parse_script`, with helper
+/// functions registered so common extraction doesn't need external
+/// `grep`/`jq` processes.
+pub fn build_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine.register_fn("regex_extract", regex_extract);
+    engine.register_fn("regex_captures", regex_captures);
+    engine.register_fn("json_field", json_field);
+    engine.register_fn("css_select", css_select);
+    engine.register_fn("xpath_select", xpath_select);
+    engine.register_fn("trim", |s: &str| s.trim().to_string());
+
+    engine
+}
+
+/// Evaluate `script` with `body` bound as the `body` variable, returning the
+/// value to diff against the prior check plus any named template vars the
+/// script exposed.
+///
+/// A script that returns a plain string (the common case) yields no extra
+/// vars. A script that wants to expose named captures (e.g. from
+/// `regex_captures`) to `Task::command`/notifier templates instead returns a
+/// map with a `text` field for the parsed value and any other fields as
+/// extra vars, e.g. `#{text: m.id, order_id: m.id}`.
+pub fn eval_parse_script(engine: &Engine, script: &str, body: &str) -> Result<(String, HashMap<String, String>)> {
+    let mut scope = rhai::Scope::new();
+    scope.push("body", body.to_string());
+
+    let result: Dynamic = engine
+        .eval_with_scope(&mut scope, script)
+        .context("Failed to evaluate parse-script")?;
+
+    if let Some(map) = result.clone().try_cast::<rhai::Map>() {
+        let text = map.get("text").map(|v| v.to_string()).unwrap_or_default();
+        let vars = map
+            .iter()
+            .filter(|(k, _)| k.as_str() != "text")
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Ok((text, vars))
+    } else {
+        Ok((result.to_string(), HashMap::new()))
+    }
+}
+
+fn regex_extract(text: &str, pattern: &str) -> String {
+    match regex::Regex::new(pattern) {
+        Ok(re) => re
+            .captures(text)
+            .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_default(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Named capture groups from the first match of `pattern` in `text`, keyed
+/// by group name (`(?<name>...)`), for scripts that want to expose more
+/// than one value to templates via `eval_parse_script`'s map return form.
+/// Unnamed groups are skipped since templates address vars by name.
+fn regex_captures(text: &str, pattern: &str) -> rhai::Map {
+    let mut map = rhai::Map::new();
+    if let Ok(re) = regex::Regex::new(pattern) {
+        if let Some(caps) = re.captures(text) {
+            for name in re.capture_names().flatten() {
+                if let Some(m) = caps.name(name) {
+                    map.insert(name.into(), Dynamic::from(m.as_str().to_string()));
+                }
+            }
+        }
+    }
+    map
+}
+
+fn json_field(text: &str, pointer: &str) -> String {
+    let parsed: serde_json::Value = match serde_json::from_str(text) {
+        Ok(v) => v,
+        Err(_) => return String::new(),
+    };
+
+    let pointer = if pointer.starts_with('/') {
+        pointer.to_string()
+    } else {
+        format!("/{}", pointer.replace('.', "/"))
+    };
+
+    parsed
+        .pointer(&pointer)
+        .map(|v| match v {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .unwrap_or_default()
+}
+
+fn css_select(html: &str, selector: &str) -> String {
+    let document = scraper::Html::parse_document(html);
+    let Ok(selector) = scraper::Selector::parse(selector) else {
+        return String::new();
+    };
+
+    document
+        .select(&selector)
+        .next()
+        .map(|el| el.text().collect::<String>())
+        .unwrap_or_default()
+}
+
+fn xpath_select(xml: &str, path: &str) -> String {
+    let Ok(package) = sxd_document::parser::parse(xml) else {
+        return String::new();
+    };
+    let document = package.as_document();
+    let Ok(value) = sxd_xpath::evaluate_xpath(&document, path) else {
+        return String::new();
+    };
+    value.string()
+}
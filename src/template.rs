@@ -0,0 +1,93 @@
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+
+/// Render a `strfmt`-style template containing `{name}` placeholders,
+/// substituting each from `vars`. Fails with a clear error naming the
+/// offending placeholder if it has no entry in `vars`, rather than silently
+/// leaving it blank or spawning a broken command.
+///
+/// A literal brace is written doubled (`{{`/`}}`), the usual `strfmt`/
+/// `str::format` convention, so commands that happen to contain braces for
+/// an unrelated reason (e.g. a JSON body) don't get misparsed as
+/// placeholders.
+pub fn render(template: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(brace) = rest.find(['{', '}']) {
+        out.push_str(&rest[..brace]);
+        let is_open = rest.as_bytes()[brace] == b'{';
+        rest = &rest[brace + 1..];
+
+        if is_open && rest.starts_with('{') {
+            out.push('{');
+            rest = &rest[1..];
+            continue;
+        }
+        if !is_open {
+            // A lone `}` (no preceding unclosed `{`) is just a literal
+            // brace; `}}` is the explicit escape for the same thing.
+            out.push('}');
+            rest = rest.strip_prefix('}').unwrap_or(rest);
+            continue;
+        }
+
+        let Some(close) = rest.find('}') else {
+            bail!("Unterminated `{{` placeholder in template: {:?}", template);
+        };
+        let key = &rest[..close];
+        rest = &rest[close + 1..];
+
+        match vars.get(key) {
+            Some(value) => out.push_str(value),
+            None => bail!("Unknown placeholder {{{}}} in template: {:?}", key, template),
+        }
+    }
+    out.push_str(rest);
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn substitutes_known_placeholders() {
+        let rendered = render("echo {url} changed: {old} -> {new}", &vars(&[
+            ("url", "https://example.com"),
+            ("old", "1"),
+            ("new", "2"),
+        ]))
+        .unwrap();
+        assert_eq!(rendered, "echo https://example.com changed: 1 -> 2");
+    }
+
+    #[test]
+    fn passes_through_text_without_placeholders() {
+        let rendered = render("echo hello world", &vars(&[])).unwrap();
+        assert_eq!(rendered, "echo hello world");
+    }
+
+    #[test]
+    fn errors_on_unknown_placeholder() {
+        let err = render("echo {missing}", &vars(&[])).unwrap_err();
+        assert!(err.to_string().contains("Unknown placeholder"));
+    }
+
+    #[test]
+    fn errors_on_unterminated_placeholder() {
+        let err = render("echo {oops", &vars(&[])).unwrap_err();
+        assert!(err.to_string().contains("Unterminated"));
+    }
+
+    #[test]
+    fn escapes_literal_braces() {
+        let rendered = render("curl -d '{{\"id\":{id}}}'", &vars(&[("id", "1")])).unwrap();
+        assert_eq!(rendered, "curl -d '{\"id\":1}'");
+    }
+}